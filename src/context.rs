@@ -2,12 +2,34 @@ use libc;
 use optee_teec_sys as raw;
 use std::ptr;
 
-use crate::{ConnectionMethods, Error, Result, Session, Uuid};
+#[cfg(feature = "thread-safe")]
+use std::sync::{Arc, RwLock};
 
+use crate::{ConnectionMethods, Error, MemFlags, Operation, Result, SharedMemory, Session, Uuid};
+
+fn validate_login(method: ConnectionMethods, connection_data: Option<&[u8]>) -> Result<()> {
+    match method {
+        ConnectionMethods::LoginPublic if connection_data.is_some() => {
+            Err(Error::from_raw_error(raw::TEEC_ERROR_BAD_PARAMETERS))
+        }
+        ConnectionMethods::LoginGroup
+        | ConnectionMethods::LoginApplication
+        | ConnectionMethods::LoginUserApplication
+        | ConnectionMethods::LoginGroupApplication
+            if connection_data.is_none() =>
+        {
+            Err(Error::from_raw_error(raw::TEEC_ERROR_BAD_PARAMETERS))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(not(feature = "thread-safe"))]
 pub struct Context {
     raw: raw::TEEC_Context,
 }
 
+#[cfg(not(feature = "thread-safe"))]
 impl Context {
     pub fn new() -> Result<Context> {
         Context::new_raw(0, true)
@@ -28,6 +50,34 @@ impl Context {
     }
 
     pub fn open_session(&mut self, uuid: Uuid) -> Result<Session> {
+        self.open_session_with_login(uuid, ConnectionMethods::LoginPublic, None, None)
+    }
+
+    /// Opens a session with a specific login method, optionally supplying the
+    /// login's connection data and/or an `Operation` carrying parameters to
+    /// pass to the Trusted Application at open time.
+    ///
+    /// `connection_data` is required for `LoginGroup`, `LoginApplication`,
+    /// `LoginUserApplication` and `LoginGroupApplication`, and must be absent
+    /// for `LoginPublic`.
+    pub fn open_session_with_login(
+        &mut self,
+        uuid: Uuid,
+        method: ConnectionMethods,
+        connection_data: Option<&[u8]>,
+        operation: Option<&Operation>,
+    ) -> Result<Session> {
+        validate_login(method, connection_data)?;
+
+        let connection_data_ptr = match connection_data {
+            Some(data) => data.as_ptr() as *const libc::c_void,
+            None => ptr::null(),
+        };
+        let operation_ptr = match operation {
+            Some(op) => op.as_mut_raw_ptr(),
+            None => ptr::null_mut(),
+        };
+
         let mut raw_session = raw::TEEC_Session {
             ctx: self.as_mut_raw_ptr(),
             session_id: 0,
@@ -38,18 +88,57 @@ impl Context {
                 self.as_mut_raw_ptr(),
                 &mut raw_session,
                 uuid.as_raw_ptr(),
-                ConnectionMethods::LoginPublic as u32,
-                ptr::null() as *const libc::c_void,
-                ptr::null_mut() as *mut raw::TEEC_Operation,
+                method as u32,
+                connection_data_ptr,
+                operation_ptr,
                 &mut err_origin,
             ) {
                 raw::TEEC_SUCCESS => Ok(Session::from_raw(raw_session)),
+                code => Err(Error::from_raw_error_with_origin(code, err_origin)),
+            }
+        }
+    }
+
+    /// Allocates a block of memory owned by the underlying implementation and
+    /// registers it for use as a Registered Memory Reference. The block is
+    /// released when the returned `SharedMemory` is dropped.
+    pub fn allocate_shared_memory(&mut self, size: usize, flags: MemFlags) -> Result<SharedMemory<'static>> {
+        let mut raw_shm = raw::TEEC_SharedMemory {
+            buffer: ptr::null_mut(),
+            size: size as libc::size_t,
+            flags: flags.bits(),
+        };
+        unsafe {
+            match raw::TEEC_AllocateSharedMemory(self.as_mut_raw_ptr(), &mut raw_shm) {
+                raw::TEEC_SUCCESS => Ok(SharedMemory::from_raw(raw_shm)),
+                code => Err(Error::from_raw_error(code)),
+            }
+        }
+    }
+
+    /// Registers an existing, caller-owned buffer as shared memory so it can
+    /// be referenced by a Registered Memory Reference without being copied.
+    /// The returned `SharedMemory` cannot outlive `buffer`.
+    pub fn register_shared_memory<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        flags: MemFlags,
+    ) -> Result<SharedMemory<'a>> {
+        let mut raw_shm = raw::TEEC_SharedMemory {
+            buffer: buffer.as_mut_ptr() as *mut libc::c_void,
+            size: buffer.len() as libc::size_t,
+            flags: flags.bits(),
+        };
+        unsafe {
+            match raw::TEEC_RegisterSharedMemory(self.as_mut_raw_ptr(), &mut raw_shm) {
+                raw::TEEC_SUCCESS => Ok(SharedMemory::from_raw(raw_shm)),
                 code => Err(Error::from_raw_error(code)),
             }
         }
     }
 }
 
+#[cfg(not(feature = "thread-safe"))]
 impl Drop for Context {
     fn drop(&mut self) {
         unsafe {
@@ -57,3 +146,202 @@ impl Drop for Context {
         }
     }
 }
+
+/// Owns the raw `TEEC_Context` on behalf of a thread-safe `Context` and every
+/// `Session` cloned from it, and finalizes it exactly once: `TEEC_Context` is
+/// only ever freed by this type's own `Drop`, which `Arc` guarantees runs
+/// when the last `Context`/`Session` sharing it goes away, whichever that is.
+#[cfg(feature = "thread-safe")]
+pub(crate) struct RawContext(pub(crate) raw::TEEC_Context);
+
+#[cfg(feature = "thread-safe")]
+impl Drop for RawContext {
+    fn drop(&mut self) {
+        unsafe {
+            raw::TEEC_FinalizeContext(&mut self.0);
+        }
+    }
+}
+
+/// Thread-safe `Context`: the raw `TEEC_Context` is guarded by an
+/// `Arc<RwLock<_>>` so it can be shared (and a session opened per worker)
+/// across threads, instead of penalizing single-threaded callers with
+/// locking by default. Clone a `Context` (a cheap `Arc` bump) and hand one
+/// clone to each worker thread that needs to open its own session. The
+/// underlying `TEEC_Context` stays alive and finalizes exactly once when the
+/// last `Context`/`Session` sharing it is dropped.
+#[cfg(feature = "thread-safe")]
+#[derive(Clone)]
+pub struct Context {
+    raw: Arc<RwLock<RawContext>>,
+}
+
+#[cfg(feature = "thread-safe")]
+impl Context {
+    pub fn new() -> Result<Context> {
+        Context::new_raw(0, true)
+    }
+
+    pub fn new_raw(fd: libc::c_int, reg_mem: bool) -> Result<Context> {
+        let mut raw_ctx = raw::TEEC_Context { fd, reg_mem };
+        unsafe {
+            match raw::TEEC_InitializeContext(ptr::null_mut() as *mut libc::c_char, &mut raw_ctx) {
+                raw::TEEC_SUCCESS => Ok(Context {
+                    raw: Arc::new(RwLock::new(RawContext(raw_ctx))),
+                }),
+                code => Err(Error::from_raw_error(code)),
+            }
+        }
+    }
+
+    pub fn open_session(&mut self, uuid: Uuid) -> Result<Session> {
+        self.open_session_with_login(uuid, ConnectionMethods::LoginPublic, None, None)
+    }
+
+    /// Opens a session with a specific login method, optionally supplying the
+    /// login's connection data and/or an `Operation` carrying parameters to
+    /// pass to the Trusted Application at open time.
+    ///
+    /// `connection_data` is required for `LoginGroup`, `LoginApplication`,
+    /// `LoginUserApplication` and `LoginGroupApplication`, and must be absent
+    /// for `LoginPublic`.
+    pub fn open_session_with_login(
+        &mut self,
+        uuid: Uuid,
+        method: ConnectionMethods,
+        connection_data: Option<&[u8]>,
+        operation: Option<&Operation>,
+    ) -> Result<Session> {
+        validate_login(method, connection_data)?;
+
+        let connection_data_ptr = match connection_data {
+            Some(data) => data.as_ptr() as *const libc::c_void,
+            None => ptr::null(),
+        };
+        let operation_ptr = match operation {
+            Some(op) => op.as_mut_raw_ptr(),
+            None => ptr::null_mut(),
+        };
+
+        let mut guard = self.raw.write().expect("TEEC_Context lock poisoned");
+        let mut raw_session = raw::TEEC_Session {
+            ctx: &mut guard.0,
+            session_id: 0,
+        };
+        let mut err_origin: libc::uint32_t = 0;
+        unsafe {
+            match raw::TEEC_OpenSession(
+                &mut guard.0,
+                &mut raw_session,
+                uuid.as_raw_ptr(),
+                method as u32,
+                connection_data_ptr,
+                operation_ptr,
+                &mut err_origin,
+            ) {
+                raw::TEEC_SUCCESS => {
+                    drop(guard);
+                    Ok(Session::from_raw(raw_session, self.raw.clone()))
+                }
+                code => Err(Error::from_raw_error_with_origin(code, err_origin)),
+            }
+        }
+    }
+
+    /// Allocates a block of memory owned by the underlying implementation and
+    /// registers it for use as a Registered Memory Reference. The block is
+    /// released when the returned `SharedMemory` is dropped.
+    pub fn allocate_shared_memory(&mut self, size: usize, flags: MemFlags) -> Result<SharedMemory<'static>> {
+        let mut raw_shm = raw::TEEC_SharedMemory {
+            buffer: ptr::null_mut(),
+            size: size as libc::size_t,
+            flags: flags.bits(),
+        };
+        let mut guard = self.raw.write().expect("TEEC_Context lock poisoned");
+        unsafe {
+            match raw::TEEC_AllocateSharedMemory(&mut guard.0, &mut raw_shm) {
+                raw::TEEC_SUCCESS => Ok(SharedMemory::from_raw(raw_shm)),
+                code => Err(Error::from_raw_error(code)),
+            }
+        }
+    }
+
+    /// Registers an existing, caller-owned buffer as shared memory so it can
+    /// be referenced by a Registered Memory Reference without being copied.
+    /// The returned `SharedMemory` cannot outlive `buffer`.
+    pub fn register_shared_memory<'a>(
+        &mut self,
+        buffer: &'a mut [u8],
+        flags: MemFlags,
+    ) -> Result<SharedMemory<'a>> {
+        let mut raw_shm = raw::TEEC_SharedMemory {
+            buffer: buffer.as_mut_ptr() as *mut libc::c_void,
+            size: buffer.len() as libc::size_t,
+            flags: flags.bits(),
+        };
+        let mut guard = self.raw.write().expect("TEEC_Context lock poisoned");
+        unsafe {
+            match raw::TEEC_RegisterSharedMemory(&mut guard.0, &mut raw_shm) {
+                raw::TEEC_SUCCESS => Ok(SharedMemory::from_raw(raw_shm)),
+                code => Err(Error::from_raw_error(code)),
+            }
+        }
+    }
+}
+
+#[cfg(all(test, feature = "thread-safe"))]
+mod tests {
+    use super::*;
+    use std::mem::{self, MaybeUninit};
+    use std::thread;
+
+    #[test]
+    fn context_clone_moves_across_thread_boundary() {
+        // No real TEE is available in this test, so the context is built
+        // from a zeroed raw struct rather than a genuine `Context::new`.
+        // `mem::forget` avoids running `Drop` (which would call
+        // `TEEC_FinalizeContext` against that fake context).
+        let raw_ctx = unsafe { MaybeUninit::<raw::TEEC_Context>::zeroed().assume_init() };
+        let ctx = Context {
+            raw: Arc::new(RwLock::new(RawContext(raw_ctx))),
+        };
+        let worker_ctx = ctx.clone();
+
+        let handle = thread::spawn(move || {
+            mem::forget(worker_ctx);
+        });
+        handle.join().unwrap();
+        mem::forget(ctx);
+    }
+
+    #[test]
+    fn context_finalizes_exactly_once_when_outlived_by_a_session() {
+        // A Session holding a clone of the Arc must be able to keep the
+        // RawContext alive past the owning Context's drop, and finalize it
+        // exactly once when the Session itself is later dropped. This uses
+        // a `Drop`-counting fake in place of `TEEC_FinalizeContext`, so it
+        // cannot use `RawContext`/`Context` directly (see the `mem::forget`
+        // tests above for why those fake out the real FFI call instead).
+        use std::cell::Cell;
+
+        thread_local! {
+            static FINALIZE_COUNT: Cell<u32> = Cell::new(0);
+        }
+
+        struct CountedDrop;
+        impl Drop for CountedDrop {
+            fn drop(&mut self) {
+                FINALIZE_COUNT.with(|c| c.set(c.get() + 1));
+            }
+        }
+
+        let shared = Arc::new(RwLock::new(CountedDrop));
+        let session_clone = shared.clone();
+
+        drop(shared);
+        assert_eq!(FINALIZE_COUNT.with(|c| c.get()), 0);
+
+        drop(session_clone);
+        assert_eq!(FINALIZE_COUNT.with(|c| c.get()), 1);
+    }
+}
@@ -0,0 +1,103 @@
+use optee_teec_sys as raw;
+use std::error;
+use std::fmt;
+
+pub type Result<T> = ::std::result::Result<T, Error>;
+
+/// Identifies which layer of the world-switch reported an `Error`, mirroring
+/// the `TEEC_ORIGIN_*` constants. Not every call surfaces an origin (e.g. a
+/// failure from `Context::new` or a client-side argument validation), in
+/// which case `Error::origin()` returns `None`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ErrorOrigin {
+    /// The error originated within the TEE Client API implementation.
+    Api,
+    /// The error originated within the underlying communication stack
+    /// between the Rich Execution Environment and the TEE.
+    Comms,
+    /// The error originated within the Trusted OS.
+    Tee,
+    /// The error originated within the Trusted Application.
+    TrustedApp,
+}
+
+impl ErrorOrigin {
+    fn from_raw(origin: u32) -> Option<ErrorOrigin> {
+        match origin {
+            raw::TEEC_ORIGIN_API => Some(ErrorOrigin::Api),
+            raw::TEEC_ORIGIN_COMMS => Some(ErrorOrigin::Comms),
+            raw::TEEC_ORIGIN_TEE => Some(ErrorOrigin::Tee),
+            raw::TEEC_ORIGIN_TRUSTED_APP => Some(ErrorOrigin::TrustedApp),
+            _ => None,
+        }
+    }
+}
+
+/// An error returned by the TEE Client API.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Error {
+    /// The call was aborted via `CancellationToken::cancel()`
+    /// (`TEEC_ERROR_CANCEL`), rather than failing outright.
+    Cancel,
+    /// Any other `TEEC_Result` failure code, carrying the origin layer
+    /// (`ErrorOrigin`) when the call surfaced one.
+    Code {
+        code: u32,
+        origin: Option<ErrorOrigin>,
+    },
+}
+
+impl Error {
+    pub fn from_raw_error(code: u32) -> Error {
+        Error::from_raw_error_with_origin(code, 0)
+    }
+
+    /// Builds an `Error` from a `TEEC_Result` code together with the
+    /// `err_origin` out-parameter reported alongside it.
+    pub fn from_raw_error_with_origin(code: u32, origin: u32) -> Error {
+        if code == raw::TEEC_ERROR_CANCEL {
+            Error::Cancel
+        } else {
+            Error::Code {
+                code,
+                origin: ErrorOrigin::from_raw(origin),
+            }
+        }
+    }
+
+    /// The raw `TEEC_Result` code as reported by the implementation.
+    pub fn code(&self) -> u32 {
+        match *self {
+            Error::Cancel => raw::TEEC_ERROR_CANCEL,
+            Error::Code { code, .. } => code,
+        }
+    }
+
+    /// Which layer of the world-switch reported this error, if known.
+    pub fn origin(&self) -> Option<ErrorOrigin> {
+        match *self {
+            Error::Cancel => None,
+            Error::Code { origin, .. } => origin,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Error::Cancel => write!(f, "TEEC operation cancelled"),
+            Error::Code { code, origin: Some(origin) } => {
+                write!(f, "TEEC error: 0x{:08x} (origin: {:?})", code, origin)
+            }
+            Error::Code { code, origin: None } => write!(f, "TEEC error: 0x{:08x}", code),
+        }
+    }
+}
+
+impl error::Error for Error {}
+
+impl From<raw::TEEC_Result> for Error {
+    fn from(code: raw::TEEC_Result) -> Error {
+        Error::from_raw_error(code)
+    }
+}
@@ -0,0 +1,40 @@
+//! # Features
+//!
+//! - `thread-safe`: switches `Context`/`Session` to variants that can be
+//!   cloned/moved across threads (see `context::Context`'s doc comment),
+//!   guarding the underlying `TEEC_Context` with a lock instead of assuming
+//!   single-threaded, exclusive access. Off by default, since it costs every
+//!   call a lock acquisition that single-threaded callers don't need.
+//!   Must be declared in the crate's `[features]` table as `thread-safe = []`.
+
+extern crate libc;
+extern crate optee_teec_sys;
+
+mod context;
+mod error;
+mod operation;
+mod parameter;
+mod session;
+mod shared_memory;
+mod uuid;
+
+pub use context::Context;
+pub use error::{Error, Result};
+pub use operation::{CancellationToken, Operation};
+pub use parameter::{ParamType, ParamTypes, Parameter, Parameters};
+pub use session::Session;
+pub use shared_memory::{MemFlags, SharedMemory};
+pub use uuid::Uuid;
+
+/// The method used to authenticate the caller to the Trusted Application
+/// when opening a session, mirroring the `TEEC_LOGIN_*` constants.
+#[repr(u32)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ConnectionMethods {
+    LoginPublic = 0x0000_0000,
+    LoginUser = 0x0000_0001,
+    LoginGroup = 0x0000_0002,
+    LoginApplication = 0x0000_0004,
+    LoginUserApplication = 0x0000_0005,
+    LoginGroupApplication = 0x0000_0006,
+}
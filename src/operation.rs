@@ -0,0 +1,146 @@
+use optee_teec_sys as raw;
+use std::cell::UnsafeCell;
+use std::marker::PhantomData;
+
+use crate::{ParamType, ParamTypes, Parameter};
+
+/// A handle that can be moved to another thread to abort an in-flight
+/// `Context::open_session_with_login`/`Session::invoke_command` call built
+/// from the `Operation` it was obtained from.
+///
+/// Calling `cancel()` issues `TEEC_RequestCancellation`; the implementation
+/// then fails the pending call with `Error::Cancel`. The `'op` lifetime ties
+/// the token to a *shared* borrow of the originating `Operation` (see
+/// `Operation`'s doc comment for why it must be shared, not exclusive), so
+/// the borrow checker still forbids dropping the operation while a token
+/// obtained from it is live.
+pub struct CancellationToken<'op> {
+    raw: *mut raw::TEEC_Operation,
+    _operation: PhantomData<&'op raw::TEEC_Operation>,
+}
+
+// SAFETY: `TEEC_RequestCancellation` only reads the `started` field, which
+// the underlying implementation and the submitting thread both access
+// through volatile/atomic means designed for concurrent cancellation.
+unsafe impl<'op> Send for CancellationToken<'op> {}
+
+impl<'op> CancellationToken<'op> {
+    pub fn cancel(&self) {
+        unsafe {
+            raw::TEEC_RequestCancellation(self.raw);
+        }
+    }
+}
+
+/// The set of parameters (and their shared flow-control block) passed to
+/// `Context::open_session_with_login` or `Session::invoke_command`.
+///
+/// The `'a` lifetime ties the `Operation` to any buffers borrowed by its
+/// `Parameter`s (see `Parameter::from_slice`), so the borrow checker forbids
+/// dropping them while the operation may still be in flight.
+///
+/// The raw `TEEC_Operation` is wrapped in an `UnsafeCell` so that submitting
+/// it (`Context::open_session_with_login`/`Session::invoke_command`) and
+/// obtaining a `CancellationToken` from it both only need a shared `&self`:
+/// `TEEC_RequestCancellation` is meant to race the blocked submit call from
+/// another thread, which an exclusive `&mut self` submit borrow would rule
+/// out entirely (the token could never be alive at the same time).
+pub struct Operation<'a> {
+    raw: UnsafeCell<raw::TEEC_Operation>,
+    param_types: ParamTypes,
+    _marker: ::std::marker::PhantomData<Parameter<'a>>,
+}
+
+impl<'a> Operation<'a> {
+    pub fn new(
+        p0: Parameter<'a>,
+        p1: Parameter<'a>,
+        p2: Parameter<'a>,
+        p3: Parameter<'a>,
+    ) -> Self {
+        let param_types = ParamTypes::new(p0.param_type, p1.param_type, p2.param_type, p3.param_type);
+        let raw = raw::TEEC_Operation {
+            // Must be 0 for the implementation to treat cancellation as
+            // enabled for this operation; see `cancellation_token`. Also
+            // reset on every submit, since the implementation sets it
+            // non-zero once the call starts (see `as_mut_raw_ptr`).
+            started: 0,
+            paramTypes: param_types.into(),
+            params: [p0.as_raw(), p1.as_raw(), p2.as_raw(), p3.as_raw()],
+        };
+        Operation {
+            raw: UnsafeCell::new(raw),
+            param_types,
+            _marker: ::std::marker::PhantomData,
+        }
+    }
+
+    /// Reads back parameter `index` (0-3), reflecting any updates the
+    /// implementation made while the operation was in flight (e.g.
+    /// `Parameter::updated_size()` for an output memory reference).
+    pub fn parameter(&self, index: usize) -> Parameter<'_> {
+        let (f0, f1, f2, f3) = self.param_types.into_flags();
+        let types: [ParamType; 4] = [f0, f1, f2, f3];
+        // SAFETY: reading a snapshot of `params[index]` by value; no other
+        // code writes through the `UnsafeCell` while `self` is reachable
+        // here (any in-flight submit call has returned by this point).
+        let params = unsafe { (*self.raw.get()).params };
+        Parameter::from_raw(params[index], types[index])
+    }
+
+    /// Obtains a `CancellationToken` that can be handed to another thread to
+    /// abort this operation once it has been submitted.
+    pub fn cancellation_token(&self) -> CancellationToken<'_> {
+        CancellationToken {
+            raw: self.raw.get(),
+            _operation: PhantomData,
+        }
+    }
+
+    /// Returns the raw pointer to submit to a `TEEC_*` call, resetting
+    /// `started` so the implementation treats this submission as
+    /// cancellable even if the `Operation` is being reused.
+    pub(crate) fn as_mut_raw_ptr(&self) -> *mut raw::TEEC_Operation {
+        let ptr = self.raw.get();
+        unsafe {
+            ::std::ptr::write_volatile(::std::ptr::addr_of_mut!((*ptr).started), 0);
+        }
+        ptr
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parameter_reads_back_correct_type_in_non_zero_slot() {
+        let mut buf = [0u8; 4];
+        let p0 = Parameter::from_value(0, 0, ParamType::ValueInput);
+        let p1 = Parameter::from_slice(&mut buf, ParamType::MemrefTempOutput);
+        let p2 = Parameter::from_value(0, 0, ParamType::None);
+        let p3 = Parameter::from_value(0, 0, ParamType::None);
+        let operation = Operation::new(p0, p1, p2, p3);
+
+        assert_eq!(operation.parameter(1).updated_size(), 4);
+    }
+
+    #[test]
+    fn cancellation_token_coexists_with_a_submit_borrow() {
+        let p0 = Parameter::from_value(0, 0, ParamType::ValueInput);
+        let p1 = Parameter::from_value(0, 0, ParamType::None);
+        let p2 = Parameter::from_value(0, 0, ParamType::None);
+        let p3 = Parameter::from_value(0, 0, ParamType::None);
+        let operation = Operation::new(p0, p1, p2, p3);
+
+        // Both `cancellation_token` and the pointer a submit call uses only
+        // need `&Operation`, so they can be held at once - this is exactly
+        // the shape `Session::invoke_command`/`token.cancel()` need to run
+        // concurrently from two threads via `std::thread::scope`.
+        std::thread::scope(|s| {
+            let token = operation.cancellation_token();
+            s.spawn(move || token.cancel());
+            let _ = operation.as_mut_raw_ptr();
+        });
+    }
+}
@@ -1,10 +1,22 @@
 use optee_teec_sys as raw;
+use std::marker::PhantomData;
 use std::mem;
 
-/// Parameters is a tuple of four Parameters.
-pub struct Parameters(pub Parameter, pub Parameter, pub Parameter, pub Parameter);
+use crate::SharedMemory;
 
-impl Parameters {
+/// Parameters is a tuple of four Parameters. The lifetime `'a` ties the tuple
+/// to any buffers borrowed by its slice-backed members (see
+/// `Parameter::from_slice`/`from_slice_ref`), so the borrow checker forbids
+/// dropping them while an `Operation` built from these `Parameters` is still
+/// outstanding.
+pub struct Parameters<'a>(
+    pub Parameter<'a>,
+    pub Parameter<'a>,
+    pub Parameter<'a>,
+    pub Parameter<'a>,
+);
+
+impl<'a> Parameters<'a> {
     pub fn new(teec_params: [raw::TEEC_Parameter; 4], param_types: u32) -> Self {
         let (f0, f1, f2, f3) = ParamTypes::from(param_types).into_flags();
         let p0 = Parameter::from_raw(teec_params[0], f0);
@@ -15,36 +27,43 @@ impl Parameters {
         Parameters(p0, p1, p2, p3)
     }
 
-    pub fn first(&self) -> &Parameter {
+    pub fn first(&self) -> &Parameter<'a> {
         &self.0
     }
 
-    pub fn second(&self) -> &Parameter {
+    pub fn second(&self) -> &Parameter<'a> {
         &self.1
     }
 
-    pub fn third(&self) -> &Parameter {
+    pub fn third(&self) -> &Parameter<'a> {
         &self.2
     }
 
-    pub fn fourth(&self) -> &Parameter {
+    pub fn fourth(&self) -> &Parameter<'a> {
         &self.3
     }
 }
 
 /// This type defines a Parameter of a Operation. It can be a Temporary Memory
 /// Reference, a Registered Memory Reference, or a Value Parameter.
-pub struct Parameter {
+///
+/// `from_tmpref`/`tmpref` hand out and read back a raw pointer with no tie to
+/// the backing buffer's lifetime; prefer `from_slice`/`from_slice_ref` for the
+/// common `&[u8]`/`&mut [u8]` case, which borrow the buffer for `'a` so it
+/// cannot be dropped while this `Parameter` is still in use.
+pub struct Parameter<'a> {
     raw: raw::TEEC_Parameter,
     pub param_type: ParamType,
+    _buffer: PhantomData<&'a mut [u8]>,
 }
 
-impl Parameter {
+impl<'a> Parameter<'a> {
     pub fn new() -> Self {
         let raw = unsafe { mem::zeroed() };
         Self {
             raw: raw,
             param_type: ParamType::None,
+            _buffer: PhantomData,
         }
     }
 
@@ -55,6 +74,7 @@ impl Parameter {
         Self {
             raw: raw,
             param_type: param_type,
+            _buffer: PhantomData,
         }
     }
 
@@ -68,6 +88,40 @@ impl Parameter {
         Self {
             raw: raw,
             param_type: param_type,
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Safe alternative to `from_tmpref` for the common `&mut [u8]` case: the
+    /// pointer and length are captured directly from the slice, and the
+    /// returned `Parameter` cannot outlive it.
+    pub fn from_slice(buffer: &'a mut [u8], param_type: ParamType) -> Self {
+        let raw = raw::TEEC_Parameter {
+            tmpref: raw::TEEC_TempMemoryReference {
+                buffer: buffer.as_mut_ptr() as *mut libc::c_void,
+                size: buffer.len() as libc::size_t,
+            },
+        };
+        Self {
+            raw: raw,
+            param_type: param_type,
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Safe alternative to `from_tmpref` for the common `&[u8]` (input-only)
+    /// case. See `from_slice`.
+    pub fn from_slice_ref(buffer: &'a [u8], param_type: ParamType) -> Self {
+        let raw = raw::TEEC_Parameter {
+            tmpref: raw::TEEC_TempMemoryReference {
+                buffer: buffer.as_ptr() as *mut libc::c_void,
+                size: buffer.len() as libc::size_t,
+            },
+        };
+        Self {
+            raw: raw,
+            param_type: param_type,
+            _buffer: PhantomData,
         }
     }
 
@@ -75,6 +129,54 @@ impl Parameter {
         Self {
             raw: raw,
             param_type: param_type,
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Builds a Registered Memory Reference parameter referring to `size`
+    /// bytes at `offset` into `shm`'s shared memory block. Use
+    /// `ParamType::MemrefWhole` to refer to the whole block, in which case
+    /// only the `parent` field is read by the implementation.
+    ///
+    /// Takes `shm` by shared reference (rather than `&mut`) so that several
+    /// memref `Parameter`s can be built against the same registered block at
+    /// once, e.g. a partial-input at offset 0 and a partial-output at offset
+    /// N for a single `Operation`.
+    pub fn from_memref(
+        shm: &'a SharedMemory<'_>,
+        offset: usize,
+        size: usize,
+        param_type: ParamType,
+    ) -> Self {
+        let raw = raw::TEEC_Parameter {
+            memref: raw::TEEC_RegisteredMemoryReference {
+                parent: shm.as_raw_ptr(),
+                size: size as libc::size_t,
+                offset: offset as libc::size_t,
+            },
+        };
+        Self {
+            raw: raw,
+            param_type: param_type,
+            _buffer: PhantomData,
+        }
+    }
+
+    /// Reads back the number of bytes the implementation reported for an
+    /// output (or inout) memory reference once the operation has completed.
+    /// Returns `0` for parameter types that are not memory references.
+    pub fn updated_size(&self) -> usize {
+        unsafe {
+            match self.param_type {
+                ParamType::MemrefTempInput | ParamType::MemrefTempOutput | ParamType::MemrefTempInout => {
+                    self.raw.tmpref.size as usize
+                }
+                ParamType::MemrefWhole
+                | ParamType::MemrefPartialInput
+                | ParamType::MemrefPartialOutput
+                | ParamType::MemrefPartialInout => self.raw.memref.size as usize,
+                _ => 0,
+            }
         }
     }
 
@@ -98,10 +200,16 @@ impl Parameter {
     pub fn set_param_type(&mut self, param_type: ParamType) {
         self.param_type = param_type;
     }
+
+    /// Returns a copy of the underlying raw union, for assembling the
+    /// `TEEC_Parameter` array of a `TEEC_Operation`.
+    pub(crate) fn as_raw(&self) -> raw::TEEC_Parameter {
+        self.raw
+    }
 }
 
-impl From<Parameter> for raw::TEEC_Parameter {
-    fn from(a: Parameter) -> raw::TEEC_Parameter {
+impl<'a> From<Parameter<'a>> for raw::TEEC_Parameter {
+    fn from(a: Parameter<'a>) -> raw::TEEC_Parameter {
         a.raw
     }
 }
@@ -168,6 +276,7 @@ impl From<u32> for ParamType {
     }
 }
 
+#[derive(Copy, Clone)]
 pub struct ParamTypes(u32);
 
 impl ParamTypes {
@@ -178,9 +287,9 @@ impl ParamTypes {
     pub fn into_flags(&self) -> (ParamType, ParamType, ParamType, ParamType) {
         (
             (0x000fu32 & self.0).into(),
-            (0x00f0u32 & self.0).into(),
-            (0x0f00u32 & self.0).into(),
-            (0xf000u32 & self.0).into(),
+            ((0x00f0u32 & self.0) >> 4).into(),
+            ((0x0f00u32 & self.0) >> 8).into(),
+            ((0xf000u32 & self.0) >> 12).into(),
         )
     }
 }
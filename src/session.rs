@@ -0,0 +1,147 @@
+use libc;
+use optee_teec_sys as raw;
+
+#[cfg(feature = "thread-safe")]
+use std::sync::{Arc, RwLock};
+
+#[cfg(feature = "thread-safe")]
+use crate::context::RawContext;
+use crate::{Error, Operation, Result};
+
+/// An open session with a Trusted Application, returned by
+/// `Context::open_session`/`Context::open_session_with_login`.
+#[cfg(not(feature = "thread-safe"))]
+pub struct Session {
+    raw: raw::TEEC_Session,
+}
+
+#[cfg(not(feature = "thread-safe"))]
+impl Session {
+    pub fn from_raw(raw: raw::TEEC_Session) -> Session {
+        Session { raw }
+    }
+
+    pub fn as_mut_raw_ptr(&mut self) -> *mut raw::TEEC_Session {
+        &mut self.raw
+    }
+
+    /// Invokes `command_id` on this session, passing `operation`'s
+    /// parameters to the Trusted Application and updating them in place with
+    /// whatever the implementation reports back.
+    pub fn invoke_command(&mut self, command_id: u32, operation: &Operation) -> Result<()> {
+        let mut err_origin: libc::uint32_t = 0;
+        unsafe {
+            match raw::TEEC_InvokeCommand(
+                self.as_mut_raw_ptr(),
+                command_id,
+                operation.as_mut_raw_ptr(),
+                &mut err_origin,
+            ) {
+                raw::TEEC_SUCCESS => Ok(()),
+                code => Err(Error::from_raw_error_with_origin(code, err_origin)),
+            }
+        }
+    }
+}
+
+#[cfg(not(feature = "thread-safe"))]
+impl Drop for Session {
+    fn drop(&mut self) {
+        unsafe {
+            raw::TEEC_CloseSession(&mut self.raw);
+        }
+    }
+}
+
+/// Thread-safe `Session`: holds a cloned `Arc` of its `Context`'s lock
+/// instead of a raw back-pointer, so the underlying `TEEC_Context` stays
+/// alive (and finalizes exactly once, via `RawContext`'s own `Drop`) for as
+/// long as this `Session` or the `Context` it was opened from exist. Each
+/// call re-acquires the lock for the duration of the underlying `TEEC_*`
+/// call (a read lock for invoking commands, a write lock for closing).
+#[cfg(feature = "thread-safe")]
+pub struct Session {
+    raw: raw::TEEC_Session,
+    ctx: Arc<RwLock<RawContext>>,
+}
+
+// SAFETY: `raw.ctx` is only ever dereferenced by `TEEC_*` calls made from
+// this module, and every such call first repoints it at the `TEEC_Context`
+// behind the lock cloned from the owning `Context` (a write lock to close,
+// a read lock to invoke - so sessions opened from the same `Context` can
+// invoke concurrently, not serialized against each other; only a concurrent
+// close is exclusive). That relies on `TEEC_InvokeCommand` itself being
+// safe to call concurrently across sessions sharing one `TEEC_Context`,
+// which is the implementation's documented behavior and the entire point
+// of this feature, even though the embedded `*mut TEEC_Context` itself
+// isn't `Send`/`Sync`.
+#[cfg(feature = "thread-safe")]
+unsafe impl Send for Session {}
+#[cfg(feature = "thread-safe")]
+unsafe impl Sync for Session {}
+
+#[cfg(feature = "thread-safe")]
+impl Session {
+    pub(crate) fn from_raw(raw: raw::TEEC_Session, ctx: Arc<RwLock<RawContext>>) -> Session {
+        Session { raw, ctx }
+    }
+
+    pub fn as_mut_raw_ptr(&mut self) -> *mut raw::TEEC_Session {
+        &mut self.raw
+    }
+
+    /// Invokes `command_id` on this session, passing `operation`'s
+    /// parameters to the Trusted Application and updating them in place with
+    /// whatever the implementation reports back.
+    pub fn invoke_command(&mut self, command_id: u32, operation: &Operation) -> Result<()> {
+        let guard = self.ctx.read().expect("TEEC_Context lock poisoned");
+        self.raw.ctx = &guard.0 as *const raw::TEEC_Context as *mut raw::TEEC_Context;
+        let mut err_origin: libc::uint32_t = 0;
+        unsafe {
+            match raw::TEEC_InvokeCommand(
+                &mut self.raw,
+                command_id,
+                operation.as_mut_raw_ptr(),
+                &mut err_origin,
+            ) {
+                raw::TEEC_SUCCESS => Ok(()),
+                code => Err(Error::from_raw_error_with_origin(code, err_origin)),
+            }
+        }
+    }
+}
+
+#[cfg(feature = "thread-safe")]
+impl Drop for Session {
+    fn drop(&mut self) {
+        let guard = self.ctx.write().expect("TEEC_Context lock poisoned");
+        self.raw.ctx = &guard.0 as *const raw::TEEC_Context as *mut raw::TEEC_Context;
+        unsafe {
+            raw::TEEC_CloseSession(&mut self.raw);
+        }
+    }
+}
+
+#[cfg(all(test, feature = "thread-safe"))]
+mod tests {
+    use super::*;
+    use std::mem::{self, MaybeUninit};
+    use std::thread;
+
+    #[test]
+    fn session_moves_across_thread_boundary() {
+        // No real TEE is available in this test, so the session is built from
+        // zeroed raw structs rather than a genuine `Context::open_session`.
+        // `mem::forget` avoids running `Drop` (which would call
+        // `TEEC_CloseSession` against that fake context).
+        let raw_session = unsafe { MaybeUninit::<raw::TEEC_Session>::zeroed().assume_init() };
+        let raw_ctx = unsafe { MaybeUninit::<raw::TEEC_Context>::zeroed().assume_init() };
+        let ctx = Arc::new(RwLock::new(RawContext(raw_ctx)));
+        let session = Session::from_raw(raw_session, ctx);
+
+        let handle = thread::spawn(move || {
+            mem::forget(session);
+        });
+        handle.join().unwrap();
+    }
+}
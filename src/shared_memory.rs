@@ -0,0 +1,84 @@
+use optee_teec_sys as raw;
+use std::marker::PhantomData;
+use std::ops::BitOr;
+use std::slice;
+
+/// Flags describing how a `SharedMemory` block may be accessed by the
+/// Trusted Application, mirroring the `TEEC_MEM_*` constants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct MemFlags(u32);
+
+impl MemFlags {
+    pub const INPUT: MemFlags = MemFlags(raw::TEEC_MEM_INPUT);
+    pub const OUTPUT: MemFlags = MemFlags(raw::TEEC_MEM_OUTPUT);
+
+    pub fn bits(self) -> u32 {
+        self.0
+    }
+}
+
+impl BitOr for MemFlags {
+    type Output = MemFlags;
+
+    fn bitor(self, rhs: MemFlags) -> MemFlags {
+        MemFlags(self.0 | rhs.0)
+    }
+}
+
+/// A block of memory registered with the underlying implementation so that it
+/// can be referenced by a Registered Memory Reference parameter without being
+/// copied for the duration of an operation.
+///
+/// Returned by `Context::allocate_shared_memory` (implementation-owned
+/// buffer) and `Context::register_shared_memory` (caller-owned buffer, whose
+/// lifetime `'a` the block cannot outlive). Released via `TEEC_ReleaseSharedMemory`
+/// on drop.
+pub struct SharedMemory<'a> {
+    raw: raw::TEEC_SharedMemory,
+    _buffer: PhantomData<&'a mut [u8]>,
+}
+
+impl<'a> SharedMemory<'a> {
+    pub(crate) fn from_raw(raw: raw::TEEC_SharedMemory) -> SharedMemory<'a> {
+        SharedMemory {
+            raw,
+            _buffer: PhantomData,
+        }
+    }
+
+    pub(crate) fn as_mut_raw_ptr(&mut self) -> *mut raw::TEEC_SharedMemory {
+        &mut self.raw
+    }
+
+    /// A `*mut` to this block's raw `TEEC_SharedMemory` obtainable from a
+    /// shared borrow, for `Parameter::from_memref`: the block is already
+    /// registered with the implementation, so multiple `Parameter`s (e.g. a
+    /// partial-input and a partial-output memref) may reference it at once.
+    pub(crate) fn as_raw_ptr(&self) -> *mut raw::TEEC_SharedMemory {
+        &self.raw as *const raw::TEEC_SharedMemory as *mut raw::TEEC_SharedMemory
+    }
+
+    pub fn size(&self) -> usize {
+        self.raw.size as usize
+    }
+
+    pub fn flags(&self) -> MemFlags {
+        MemFlags(self.raw.flags)
+    }
+
+    pub fn as_slice(&self) -> &[u8] {
+        unsafe { slice::from_raw_parts(self.raw.buffer as *const u8, self.size()) }
+    }
+
+    pub fn as_mut_slice(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(self.raw.buffer as *mut u8, self.size()) }
+    }
+}
+
+impl<'a> Drop for SharedMemory<'a> {
+    fn drop(&mut self) {
+        unsafe {
+            raw::TEEC_ReleaseSharedMemory(&mut self.raw);
+        }
+    }
+}
@@ -0,0 +1,30 @@
+use optee_teec_sys as raw;
+
+/// The identifier of a Trusted Application, as defined by the GlobalPlatform
+/// TEE Client API (a standard 128-bit UUID).
+#[derive(Copy, Clone, Debug)]
+pub struct Uuid {
+    raw: raw::TEEC_UUID,
+}
+
+impl Uuid {
+    pub fn new(
+        time_low: u32,
+        time_mid: u16,
+        time_hi_and_version: u16,
+        clock_seq_and_node: [u8; 8],
+    ) -> Uuid {
+        Uuid {
+            raw: raw::TEEC_UUID {
+                timeLow: time_low,
+                timeMid: time_mid,
+                timeHiAndVersion: time_hi_and_version,
+                clockSeqAndNode: clock_seq_and_node,
+            },
+        }
+    }
+
+    pub fn as_raw_ptr(&self) -> *const raw::TEEC_UUID {
+        &self.raw
+    }
+}